@@ -7,73 +7,531 @@
 //! without the key. The harder to guess the key, the harder to decode/decode
 //! data.
 //!
-//! This crate is not by any means cryptographically secure, it was designed to
-//! be fast and to be compatible with the slower scripting languages. Do not
-//! rely on to encrypt any sensible data, it was designed to be used as a simple
-//! obfuscation method.
+//! [`Base64::new`] (and the plain key-sorted alphabet it builds on) is not by
+//! any means cryptographically secure — it was designed to be fast and to be
+//! compatible with the slower scripting languages, as a simple obfuscation
+//! method rather than encryption. Do not rely on it to protect sensitive data.
+//!
+//! Callers who need real confidentiality should use [`Base64::new_encrypted`]
+//! instead, which authenticates and encrypts the payload with
+//! ChaCha20-Poly1305 (under a key derived from the passphrase via HKDF-SHA256)
+//! before it is run through the same alphabet transform. That path is gated
+//! behind the `secure` feature and gives the usual AEAD guarantees —
+//! confidentiality and tamper detection — as long as the passphrase itself is
+//! kept secret.
 #![deny(missing_docs)]
 #![allow(warnings)]
 
-pub use base64::DecodeError as Error;
+#[cfg(feature = "secure")]
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+#[cfg(feature = "secure")]
+use hkdf::Hkdf;
+#[cfg(feature = "secure")]
+use sha2::Sha256;
+
 use base64::{alphabet, engine, Engine};
+use std::io::{self, Read, Write};
+
+/// Format version of the self-describing header written by [`Base64::new_framed`].
+const FRAME_VERSION: u8 = 1;
+/// Length in bytes of the key fingerprint stored in the header.
+const FINGERPRINT_LEN: usize = 4;
+/// Total length in bytes of the header (version byte + fingerprint).
+const HEADER_LEN: usize = 1 + FINGERPRINT_LEN;
+/// Length in bytes of the random salt prepended by [`Base64::encode_salted`].
+///
+/// A full 64 bits of random salt keeps the odds of two calls reusing the
+/// same keystream (and thus leaking `pt1 XOR pt2`) negligible even for
+/// high-volume callers, unlike a short salt which collides far sooner.
+const SALT_LEN: usize = 8;
+/// The 62 alphanumeric characters shared by every [`Charset`]; each charset
+/// appends its own two non-alphanumeric symbols to complete the alphabet.
+const ALPHANUMERIC: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// The two non-alphanumeric characters used to complete the 64-symbol base
+/// alphabet, selectable via [`Base64Builder::charset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// The standard base64 characters: `+` and `/`.
+    Standard,
+    /// The URL- and filename-safe characters: `-` and `_`. This is what
+    /// [`Base64::new`] uses.
+    UrlSafe,
+    /// The crypt(3)-style characters: `.` and `/`.
+    Crypt,
+}
+
+impl Charset {
+    fn symbols(self) -> &'static str {
+        match self {
+            Charset::Standard => "+/",
+            Charset::UrlSafe => "-_",
+            Charset::Crypt => "./",
+        }
+    }
+}
+
+/// Error returned when decoding fails.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying base64 payload could not be decoded.
+    Decode(base64::DecodeError),
+    /// The key fingerprint embedded in a framed payload did not match the
+    /// key this instance was constructed with.
+    WrongKey,
+    /// The decoded payload was shorter than the salt prepended by
+    /// [`Base64::encode_salted`].
+    Truncated,
+    /// The ChaCha20-Poly1305 authentication tag did not match the ciphertext;
+    /// either the key is wrong or the data was tampered with.
+    #[cfg(feature = "secure")]
+    Decrypt,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Decode(err) => write!(f, "{err}"),
+            Error::WrongKey => write!(f, "key fingerprint mismatch"),
+            Error::Truncated => write!(f, "payload too short to contain the expected salt"),
+            #[cfg(feature = "secure")]
+            Error::Decrypt => write!(f, "decryption failed: wrong key or tampered data"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
 
 /// Base64 encoder/decoder with custom alphabet.
 ///
-/// The alphabet is sorted by a given key and their weights are calculated by
-/// the CRC32 hash of each character.
+/// The alphabet is a permutation of the 62 alphanumeric characters plus the
+/// two symbols of the chosen [`Charset`], shuffled by a key-seeded Fisher-Yates shuffle so the whole ordering depends on the
+/// key.
 ///
 /// This class will provide a custom alphabet for the base64 encoder/decoder,
 /// which makes virtually impossible to decode the encoded data without the key.
 pub struct Base64 {
     /// Base64 engine.
     engine: engine::GeneralPurpose,
+    /// ChaCha20-Poly1305 cipher used when constructed via [`Base64::new_encrypted`].
+    #[cfg(feature = "secure")]
+    cipher: Option<ChaCha20Poly1305>,
+    /// Key fingerprint written to (and checked against) a self-describing
+    /// header when constructed via [`Base64::new_framed`].
+    fingerprint: Option<[u8; FINGERPRINT_LEN]>,
+    /// CRC32 of the key, used to seed the keystream in
+    /// [`Base64::encode_salted`]/[`Base64::decode_salted`].
+    key_hash: u32,
 }
 
 impl Base64 {
     /// Create a new Base64 encoder/decoder with a custom key to sort the alphabet.
     pub fn new(key: &[u8]) -> Self {
-        let alphabet = alphabet::Alphabet::new(&Self::get_alphabet(key)).expect("alphabet");
-        let config = engine::GeneralPurposeConfig::new()
-            .with_decode_allow_trailing_bits(false)
-            .with_encode_padding(false)
-            .with_decode_padding_mode(engine::DecodePaddingMode::RequireNone);
+        Self::builder(key).build()
+    }
 
-        Self {
-            engine: engine::GeneralPurpose::new(&alphabet, config),
-        }
+    /// Start building a [`Base64`] encoder/decoder with a custom charset,
+    /// padding and trailing-bit configuration on top of the key-driven
+    /// alphabet permutation.
+    pub fn builder(key: &[u8]) -> Base64Builder {
+        Base64Builder::new(key)
     }
 
-    /// Get a custom alphabet sorted by the given key.
-    fn get_alphabet(key: &[u8]) -> String {
+    /// Create a new Base64 encoder/decoder that also encrypts the data with
+    /// ChaCha20-Poly1305 before it is run through the key-sorted alphabet.
+    ///
+    /// A 256-bit key is derived from `passphrase` via HKDF-SHA256. Each call
+    /// to [`Base64::encode`] generates a fresh random nonce, so encoding the
+    /// same plaintext twice yields different output.
+    #[cfg(feature = "secure")]
+    pub fn new_encrypted(passphrase: &[u8]) -> Self {
+        let mut base = Self::new(passphrase);
+
+        let mut key_bytes = [0u8; 32];
+        Hkdf::<Sha256>::new(None, passphrase)
+            .expand(b"base64-secret-rs encrypted", &mut key_bytes)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        base.cipher = Some(ChaCha20Poly1305::new(Key::from_slice(&key_bytes)));
+
+        base
+    }
+
+    /// Create a new Base64 encoder/decoder that prepends a self-describing
+    /// header — a format version and a key fingerprint — to the encoded
+    /// payload.
+    ///
+    /// This lets [`Base64::decode`] reject a wrong key immediately with
+    /// [`Error::WrongKey`], instead of only failing incidentally while
+    /// interpreting a garbled body.
+    pub fn new_framed(key: &[u8]) -> Self {
+        let mut base = Self::new(key);
+        base.fingerprint = Some(crc32fast::hash(key).to_be_bytes());
+        base
+    }
+
+    /// Get `base` (the 64-character alphanumeric-plus-charset string)
+    /// shuffled by the given key.
+    ///
+    /// A 64-bit seed is derived from the CRC32 of the key and of the reversed
+    /// key, which then drives a splitmix64 PRNG. `base` is permuted in place
+    /// with a Fisher-Yates shuffle, giving a uniform, fully key-dependent
+    /// ordering with no tie-breaking ambiguity.
+    fn get_alphabet(key: &[u8], base: &str) -> String {
         let rev_key = key.iter().cloned().rev().collect::<Vec<_>>();
         let hash = crc32fast::hash(key);
         let rev_hash = crc32fast::hash(&rev_key);
 
-        let mut alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
-            .chars()
-            .enumerate()
-            .map(|(i, c)| {
-                (
-                    c,
-                    crc32fast::hash(c.to_string().as_bytes())
-                        % if i % 2 == 0 { hash } else { rev_hash },
-                )
-            })
-            .collect::<Vec<(char, u32)>>();
+        let seed = (((hash as u64) << 32) | rev_hash as u64).max(1);
+        let mut rng = seed;
+
+        let mut alphabet: Vec<char> = base.chars().collect();
+
+        for i in (1..alphabet.len()).rev() {
+            let j = (Self::next_rand(&mut rng) % (i as u64 + 1)) as usize;
+            alphabet.swap(i, j);
+        }
+
+        alphabet.into_iter().collect::<String>()
+    }
+
+    /// Advance a splitmix64 PRNG state and return the next pseudo-random value.
+    fn next_rand(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Encrypt `input` when this instance was constructed via
+    /// [`Base64::new_encrypted`], otherwise return it unchanged.
+    #[cfg(feature = "secure")]
+    fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        match &self.cipher {
+            Some(cipher) => {
+                let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+                let ciphertext = cipher
+                    .encrypt(&nonce, input)
+                    .expect("chacha20poly1305 encryption failure");
+
+                let mut payload = nonce.to_vec();
+                payload.extend_from_slice(&ciphertext);
+                payload
+            }
+            None => input.to_vec(),
+        }
+    }
 
-        alphabet.sort_by(|a, b| b.1.cmp(&a.1));
+    #[cfg(not(feature = "secure"))]
+    fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        input.to_vec()
+    }
+
+    /// Reverse [`Base64::encrypt`].
+    #[cfg(feature = "secure")]
+    fn decrypt(&self, payload: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match &self.cipher {
+            Some(cipher) => {
+                if payload.len() < 12 {
+                    return Err(Error::Decrypt);
+                }
+                let (nonce, ciphertext) = payload.split_at(12);
+                cipher
+                    .decrypt(Nonce::from_slice(nonce), ciphertext)
+                    .map_err(|_| Error::Decrypt)
+            }
+            None => Ok(payload),
+        }
+    }
+
+    #[cfg(not(feature = "secure"))]
+    fn decrypt(&self, payload: Vec<u8>) -> Result<Vec<u8>, Error> {
+        Ok(payload)
+    }
 
-        alphabet.iter().map(|a| a.0).collect::<String>()
+    /// Whether this instance was constructed via [`Base64::new_encrypted`].
+    #[cfg(feature = "secure")]
+    fn has_cipher(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    #[cfg(not(feature = "secure"))]
+    fn has_cipher(&self) -> bool {
+        false
     }
 
     /// Encode the given input with the custom alphabet.
+    ///
+    /// When constructed via [`Base64::new_encrypted`], the input is
+    /// encrypted with ChaCha20-Poly1305 under a fresh random nonce first.
+    /// When constructed via [`Base64::new_framed`], a header carrying a key
+    /// fingerprint is prepended before the final alphabet encoding.
     pub fn encode<T: AsRef<[u8]>>(&self, input: T) -> String {
-        self.engine.encode(input)
+        let mut payload = self.encrypt(input.as_ref());
+
+        if let Some(fingerprint) = &self.fingerprint {
+            let mut framed = Vec::with_capacity(HEADER_LEN + payload.len());
+            framed.push(FRAME_VERSION);
+            framed.extend_from_slice(fingerprint);
+            framed.append(&mut payload);
+            payload = framed;
+        }
+
+        self.engine.encode(payload)
     }
 
     /// Decode the given input with the custom alphabet.
+    ///
+    /// When constructed via [`Base64::new_framed`], the header is checked
+    /// first and [`Error::WrongKey`] is returned immediately on a
+    /// fingerprint mismatch, before the body is interpreted. When
+    /// constructed via [`Base64::new_encrypted`], the remaining payload is
+    /// then authenticated and decrypted.
     pub fn decode<T: AsRef<[u8]>>(&self, input: T) -> Result<Vec<u8>, Error> {
-        self.engine.decode(input)
+        let input = input.as_ref();
+
+        if let Some(fingerprint) = &self.fingerprint {
+            // The first 8 symbols decode to 6 whole bytes on their own, which
+            // covers the header, so it is checked before the rest is decoded.
+            if input.len() >= 8 {
+                let head = self.engine.decode(&input[..8]).map_err(Error::Decode)?;
+                if head[0] != FRAME_VERSION || head[1..HEADER_LEN] != *fingerprint {
+                    return Err(Error::WrongKey);
+                }
+            }
+        }
+
+        let mut payload = self.engine.decode(input).map_err(Error::Decode)?;
+
+        if let Some(fingerprint) = &self.fingerprint {
+            if payload.len() < HEADER_LEN
+                || payload[0] != FRAME_VERSION
+                || payload[1..HEADER_LEN] != *fingerprint
+            {
+                return Err(Error::WrongKey);
+            }
+            payload = payload.split_off(HEADER_LEN);
+        }
+
+        self.decrypt(payload)
+    }
+
+    /// Wrap `writer` so that bytes written to it are encoded with this
+    /// instance's custom alphabet and forwarded to `writer` as they are
+    /// produced, without buffering the whole payload in memory.
+    ///
+    /// When constructed via [`Base64::new_framed`], the header is written
+    /// to `writer` up front, before any caller-supplied bytes — the header
+    /// is a handful of bytes, so this does not require buffering the
+    /// payload either.
+    ///
+    /// The returned writer must be finished with its `finish()` method (or
+    /// dropped) to flush any buffered trailing bytes and padding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this instance was constructed via [`Base64::new_encrypted`].
+    /// ChaCha20-Poly1305 needs the whole plaintext up front to produce its
+    /// authentication tag, so encryption genuinely cannot be streamed one
+    /// chunk at a time; there is no way to honor it here without buffering
+    /// the entire payload, which would defeat the point of this method.
+    pub fn encoder<W: Write>(&self, writer: W) -> io::Result<EncoderWriter<'_, W>> {
+        self.assert_not_encrypted();
+        let mut encoder = base64::write::EncoderWriter::new(writer, &self.engine);
+        if let Some(fingerprint) = &self.fingerprint {
+            encoder.write_all(&[FRAME_VERSION])?;
+            encoder.write_all(fingerprint)?;
+        }
+        Ok(encoder)
+    }
+
+    /// Wrap `reader` so that bytes read from it are decoded with this
+    /// instance's custom alphabet as they are consumed, without buffering
+    /// the whole payload in memory.
+    ///
+    /// When constructed via [`Base64::new_framed`], the header is read and
+    /// checked against this instance's key fingerprint up front; an
+    /// [`std::io::Error`] of kind [`std::io::ErrorKind::InvalidData`] is
+    /// returned immediately on a mismatch, before any payload bytes reach
+    /// the caller.
+    ///
+    /// # Panics
+    ///
+    /// See [`Base64::encoder`]: this panics under the same condition.
+    pub fn decoder<R: Read>(&self, reader: R) -> io::Result<DecoderReader<'_, R>> {
+        self.assert_not_encrypted();
+        let mut decoder = base64::read::DecoderReader::new(reader, &self.engine);
+        if let Some(fingerprint) = &self.fingerprint {
+            let mut header = [0u8; HEADER_LEN];
+            decoder.read_exact(&mut header)?;
+            if header[0] != FRAME_VERSION || header[1..] != *fingerprint {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "key fingerprint mismatch",
+                ));
+            }
+        }
+        Ok(decoder)
+    }
+
+    /// Panic if this instance was constructed via [`Base64::new_encrypted`],
+    /// since [`Base64::encoder`]/[`Base64::decoder`] only stream the alphabet
+    /// transform (plus, for [`Base64::new_framed`], the small fixed-size
+    /// header) and cannot stream AEAD encryption without buffering the whole
+    /// payload.
+    fn assert_not_encrypted(&self) {
+        assert!(
+            !self.has_cipher(),
+            "Base64::encoder/decoder cannot be used on an instance built with new_encrypted: \
+             ChaCha20-Poly1305 needs the whole plaintext up front, so it cannot be streamed \
+             without buffering the entire payload"
+        );
+    }
+
+    /// Encode `input` with a fresh random salt folded into a keystream that
+    /// is XORed over the plaintext before the usual alphabet encoding.
+    ///
+    /// Unlike [`Base64::encode`], encoding the same message twice produces
+    /// different output, which defeats the pattern/ECB-style analysis that
+    /// a fixed key-to-alphabet mapping is otherwise exposed to. This is a
+    /// lightweight alternative to the `secure` feature: the keystream is
+    /// not authenticated, so tampering is not detected.
+    pub fn encode_salted<T: AsRef<[u8]>>(&self, input: T) -> String {
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::getrandom(&mut salt).expect("failed to get random salt");
+
+        let mut payload = Vec::with_capacity(SALT_LEN + input.as_ref().len());
+        payload.extend_from_slice(&salt);
+        payload.extend(Self::xor_keystream(self.key_hash, salt, input.as_ref()));
+
+        self.engine.encode(payload)
+    }
+
+    /// Decode data produced by [`Base64::encode_salted`].
+    pub fn decode_salted<T: AsRef<[u8]>>(&self, input: T) -> Result<Vec<u8>, Error> {
+        let payload = self.engine.decode(input).map_err(Error::Decode)?;
+        if payload.len() < SALT_LEN {
+            return Err(Error::Truncated);
+        }
+
+        let (salt, ciphertext) = payload.split_at(SALT_LEN);
+        let salt: [u8; SALT_LEN] = salt.try_into().expect("checked length above");
+        Ok(Self::xor_keystream(self.key_hash, salt, ciphertext))
+    }
+
+    /// XOR `data` with a keystream drawn from a splitmix64 PRNG seeded by
+    /// `key_hash` combined with `salt`, used by
+    /// [`Base64::encode_salted`]/[`Base64::decode_salted`].
+    ///
+    /// The 64-bit salt is folded in with `key_hash` spread across both
+    /// halves of the seed and then run through one splitmix64 step, so the
+    /// combined seed doesn't just echo `key_hash`'s 32 bits in the upper or
+    /// lower half of the state.
+    fn xor_keystream(key_hash: u32, salt: [u8; SALT_LEN], data: &[u8]) -> Vec<u8> {
+        let salt_seed = u64::from_be_bytes(salt);
+        let key_seed = (key_hash as u64) << 32 | key_hash as u64;
+        let mut seed = (salt_seed ^ key_seed).max(1);
+        let mut rng = Self::next_rand(&mut seed);
+
+        data.iter()
+            .map(|&b| b ^ Self::next_rand(&mut rng) as u8)
+            .collect()
+    }
+}
+
+/// A [`Write`] adapter that encodes bytes with a [`Base64`] instance's
+/// custom alphabet as they are written, returned by [`Base64::encoder`].
+pub type EncoderWriter<'a, W> = base64::write::EncoderWriter<'a, engine::GeneralPurpose, W>;
+
+/// A [`Read`] adapter that decodes bytes with a [`Base64`] instance's
+/// custom alphabet as they are read, returned by [`Base64::decoder`].
+pub type DecoderReader<'a, R> = base64::read::DecoderReader<'a, engine::GeneralPurpose, R>;
+
+/// Builder for [`Base64`], returned by [`Base64::builder`].
+///
+/// Lets callers choose the two non-alphanumeric charset symbols, whether
+/// `=` padding is used, and whether trailing bits are allowed when
+/// decoding, on top of the usual key-driven alphabet permutation.
+pub struct Base64Builder {
+    key: Vec<u8>,
+    charset: Charset,
+    padding: bool,
+    allow_trailing_bits: bool,
+}
+
+impl Base64Builder {
+    fn new(key: &[u8]) -> Self {
+        Self {
+            key: key.to_vec(),
+            charset: Charset::UrlSafe,
+            padding: false,
+            allow_trailing_bits: false,
+        }
+    }
+
+    /// Replace the key used to permute the alphabet.
+    pub fn key(mut self, key: &[u8]) -> Self {
+        self.key = key.to_vec();
+        self
+    }
+
+    /// Choose the two non-alphanumeric characters from a preset [`Charset`].
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Shorthand for `.charset(Charset::UrlSafe)` when `true`, or
+    /// `.charset(Charset::Standard)` when `false`.
+    pub fn url_safe(mut self, url_safe: bool) -> Self {
+        self.charset = if url_safe {
+            Charset::UrlSafe
+        } else {
+            Charset::Standard
+        };
+        self
+    }
+
+    /// Toggle `=` padding: when enabled, `encode` appends padding and
+    /// `decode` requires it to be present and canonical; when disabled
+    /// (the default, matching [`Base64::new`]), `decode` requires its
+    /// absence.
+    pub fn padding(mut self, padding: bool) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Toggle whether trailing bits in the last symbol are allowed when
+    /// decoding, rather than rejected as malformed input.
+    pub fn allow_trailing_bits(mut self, allow_trailing_bits: bool) -> Self {
+        self.allow_trailing_bits = allow_trailing_bits;
+        self
+    }
+
+    /// Build the configured [`Base64`] encoder/decoder.
+    pub fn build(self) -> Base64 {
+        let base = format!("{ALPHANUMERIC}{}", self.charset.symbols());
+        let alphabet =
+            alphabet::Alphabet::new(&Base64::get_alphabet(&self.key, &base)).expect("alphabet");
+        let config = engine::GeneralPurposeConfig::new()
+            .with_decode_allow_trailing_bits(self.allow_trailing_bits)
+            .with_encode_padding(self.padding)
+            .with_decode_padding_mode(if self.padding {
+                engine::DecodePaddingMode::RequireCanonical
+            } else {
+                engine::DecodePaddingMode::RequireNone
+            });
+
+        Base64 {
+            engine: engine::GeneralPurpose::new(&alphabet, config),
+            #[cfg(feature = "secure")]
+            cipher: None,
+            fingerprint: None,
+            key_hash: crc32fast::hash(&self.key),
+        }
     }
 }
 
@@ -101,7 +559,208 @@ mod test {
     #[test]
     fn expected_data() {
         let x = Base64::new(b"long and random key\0test\0");
-        let result = x.decode("t0mvt-").expect("decode");
+        let result = x.decode("SmVkSg").expect("decode");
         assert_eq!("test".to_owned(), String::from_utf8_lossy(&result));
     }
+
+    #[test]
+    fn framed_round_trip() {
+        let x = Base64::new_framed(b"test");
+        let encoded = x.encode("test");
+        let decoded = x.decode(encoded.as_bytes()).expect("success");
+        assert_eq!("test".to_owned(), String::from_utf8_lossy(&decoded));
+    }
+
+    #[test]
+    fn framed_wrong_key_is_rejected_immediately() {
+        let x = Base64::new_framed(b"test");
+        let y = Base64::new_framed(b"test1");
+        let encoded = x.encode("test");
+        assert!(matches!(y.decode(encoded.as_bytes()), Err(Error::WrongKey)));
+        assert!(x.decode(encoded.as_bytes()).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "secure")]
+    fn encrypted_round_trip() {
+        let x = Base64::new_encrypted(b"test passphrase");
+        let encoded = x.encode("a secret message");
+        let decoded = x.decode(encoded.as_bytes()).expect("success");
+        assert_eq!(
+            "a secret message".to_owned(),
+            String::from_utf8_lossy(&decoded)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "secure")]
+    fn encrypted_distinct_ciphertext() {
+        let x = Base64::new_encrypted(b"test passphrase");
+        assert_ne!(x.encode("same message"), x.encode("same message"));
+    }
+
+    #[test]
+    #[cfg(feature = "secure")]
+    fn encrypted_wrong_key() {
+        let x = Base64::new_encrypted(b"test passphrase");
+        let y = Base64::new_encrypted(b"other passphrase");
+        let encoded = x.encode("a secret message");
+        assert!(y.decode(encoded.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn streaming_encoder_matches_encode() {
+        let x = Base64::new(b"test");
+        // Lengths on either side of a 3-byte group boundary.
+        for data in [
+            "",
+            "a",
+            "ab",
+            "abc",
+            "abcd",
+            "abcde",
+            "a longer message than one chunk",
+        ] {
+            let mut encoder = x.encoder(Vec::new()).expect("not an encrypted instance");
+            // Write in small, unaligned chunks to exercise boundary handling.
+            for chunk in data.as_bytes().chunks(2) {
+                encoder.write_all(chunk).expect("write");
+            }
+            let written = encoder.finish().expect("finish");
+            assert_eq!(x.encode(data), String::from_utf8(written).unwrap());
+        }
+    }
+
+    #[test]
+    fn streaming_decoder_matches_decode() {
+        let x = Base64::new(b"test");
+        for data in [
+            "",
+            "a",
+            "ab",
+            "abc",
+            "abcd",
+            "abcde",
+            "a longer message than one chunk",
+        ] {
+            let encoded = x.encode(data);
+            let mut decoder = x
+                .decoder(encoded.as_bytes())
+                .expect("not an encrypted instance");
+            let mut result = Vec::new();
+            // Read back in small, unaligned chunks.
+            let mut buf = [0u8; 2];
+            loop {
+                let n = decoder.read(&mut buf).expect("read");
+                if n == 0 {
+                    break;
+                }
+                result.extend_from_slice(&buf[..n]);
+            }
+            assert_eq!(data.as_bytes(), &result[..]);
+        }
+    }
+
+    #[test]
+    fn streaming_framed_round_trip() {
+        let x = Base64::new_framed(b"test");
+        let mut encoder = x.encoder(Vec::new()).expect("not an encrypted instance");
+        encoder.write_all(b"a secret message").expect("write");
+        let written = encoder.finish().expect("finish");
+
+        let mut decoder = x.decoder(&written[..]).expect("not an encrypted instance");
+        let mut result = Vec::new();
+        decoder.read_to_end(&mut result).expect("read");
+        assert_eq!(result, b"a secret message");
+    }
+
+    #[test]
+    fn streaming_framed_wrong_key_is_rejected_immediately() {
+        let x = Base64::new_framed(b"test");
+        let y = Base64::new_framed(b"other");
+        let mut encoder = x.encoder(Vec::new()).expect("not an encrypted instance");
+        encoder.write_all(b"a secret message").expect("write");
+        let written = encoder.finish().expect("finish");
+
+        let err = y.decoder(&written[..]).expect_err("fingerprint mismatch");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[should_panic(expected = "new_encrypted")]
+    #[cfg(feature = "secure")]
+    fn encoder_panics_on_encrypted_instance() {
+        let x = Base64::new_encrypted(b"test");
+        let _ = x.encoder(Vec::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "new_encrypted")]
+    #[cfg(feature = "secure")]
+    fn decoder_panics_on_encrypted_instance() {
+        let x = Base64::new_encrypted(b"test");
+        let _ = x.decoder(&b""[..]);
+    }
+
+    #[test]
+    fn builder_default_matches_new() {
+        let x = Base64::new(b"test");
+        let y = Base64::builder(b"test").build();
+        assert_eq!(x.encode("test"), y.encode("test"));
+    }
+
+    #[test]
+    fn builder_standard_charset_round_trips() {
+        let x = Base64::builder(b"test").url_safe(false).build();
+        let encoded = x.encode("test");
+        let decoded = x.decode(encoded.as_bytes()).expect("decode");
+        assert_eq!(b"test", &decoded[..]);
+    }
+
+    #[test]
+    fn builder_padding_appends_equals() {
+        let x = Base64::builder(b"test").padding(true).build();
+        let encoded = x.encode("a");
+        assert!(encoded.ends_with('='));
+        let decoded = x.decode(encoded.as_bytes()).expect("decode");
+        assert_eq!(b"a", &decoded[..]);
+    }
+
+    #[test]
+    fn builder_crypt_charset_round_trips() {
+        let x = Base64::builder(b"test").charset(Charset::Crypt).build();
+        let encoded = x.encode("test");
+        let decoded = x.decode(encoded.as_bytes()).expect("decode");
+        assert_eq!(b"test", &decoded[..]);
+    }
+
+    #[test]
+    fn salted_round_trip() {
+        let x = Base64::new(b"test");
+        let encoded = x.encode_salted("test");
+        let decoded = x.decode_salted(encoded.as_bytes()).expect("success");
+        assert_eq!("test".to_owned(), String::from_utf8_lossy(&decoded));
+    }
+
+    #[test]
+    fn salted_output_differs_across_calls() {
+        let x = Base64::new(b"test");
+        assert_ne!(
+            x.encode_salted("same message"),
+            x.encode_salted("same message")
+        );
+    }
+
+    #[test]
+    fn salted_wrong_key_does_not_recover_plaintext() {
+        let x = Base64::new(b"test");
+        let y = Base64::new(b"test1");
+        let encoded = x.encode_salted("test");
+        // The mismatched alphabet may reject the payload outright, or
+        // decode it into garbage; either way the plaintext must not come
+        // back out.
+        if let Ok(decoded) = y.decode_salted(encoded.as_bytes()) {
+            assert_ne!(decoded, b"test");
+        }
+    }
 }